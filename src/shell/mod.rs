@@ -7,10 +7,23 @@
 //! This abstraction only manages the protocol part of shell surfaces. If you're
 //! looking for a more battery-included abstraction for creating windows,
 //! consider the `Window` type.
+//!
+//! This module intentionally does not expose a "is this surface responding"
+//! event. `xdg_wm_base.ping`/`zxdg_shell_v6.ping` are per-client liveness
+//! checks answered wherever the corresponding global is bound, not events
+//! on a `xdg_toplevel`/`zxdg_toplevel_v6` surface, so there is no per-surface
+//! ping traffic here to build such a feature on top of; it would have to be
+//! implemented where the shell globals themselves are bound. `wl_shell`
+//! surfaces do receive a per-surface `ping`, but tracking responsiveness
+//! from the gap between pings (rather than actual reply latency) would
+//! flag idle-but-healthy clients as unresponsive, so it is not worth doing
+//! for that one legacy backend alone.
 use wayland_client::protocol::{wl_output, wl_seat, wl_surface};
 use wayland_client::Proxy;
 
-use wayland_protocols::xdg_shell::client::xdg_toplevel;
+use wayland_protocols::unstable::xdg_shell::v6::client::{zxdg_surface_v6, zxdg_toplevel_v6};
+use wayland_protocols::xdg_shell::client::{xdg_surface, xdg_toplevel};
+pub use wayland_protocols::xdg_shell::client::xdg_positioner::{Anchor, ConstraintAdjustment, Gravity};
 pub use wayland_protocols::xdg_shell::client::xdg_toplevel::State;
 
 use Shell;
@@ -40,6 +53,19 @@ pub enum Event {
         /// Typically tells you if your surface is active/inactive, maximized,
         /// etc...
         states: Vec<State>,
+        /// Compositor-suggested bounds for the window
+        ///
+        /// This is the maximum size the compositor recommends for your
+        /// shell surface (for example, the usable area of the output it is
+        /// on), which you can use to choose a sensible default size. It is
+        /// a hint only: you are not required to constrain yourself to it,
+        /// and it may be smaller than `new_size` during an interactive
+        /// resize.
+        ///
+        /// `None` if the compositor did not provide this information, which
+        /// is always the case on `wl_shell` and on older `zxdg_shell_v6`
+        /// compositors.
+        bounds: Option<(u32, u32)>,
     },
     /// A close request has been received
     ///
@@ -48,6 +74,175 @@ pub enum Event {
     Close,
 }
 
+/// Possible events generated by a popup surface that you need to handle
+#[derive(Clone, Debug)]
+pub enum PopupEvent {
+    /// The compositor has (re)configured the popup
+    Configure {
+        /// New x position of the popup, relative to its parent
+        x: i32,
+        /// New y position of the popup, relative to its parent
+        y: i32,
+        /// New width of the popup
+        width: u32,
+        /// New height of the popup
+        height: u32,
+    },
+    /// The compositor has dismissed the popup
+    ///
+    /// This happens for example when the user clicks outside of the
+    /// popup's implicit grab. You should destroy the popup surface in
+    /// response to this event.
+    PopupDone,
+}
+
+/// A protocol-agnostic description of how a popup should be positioned
+///
+/// This mirrors the semantics of `xdg_positioner`: an anchor rectangle
+/// expressed in the parent surface's local coordinates, the edge (or
+/// corner) of that rectangle the popup is anchored to, the direction in
+/// which the popup grows away from the anchor point, the size of the
+/// popup itself, and the adjustments the compositor is allowed to make
+/// to keep it on-screen.
+#[derive(Copy, Clone, Debug)]
+pub struct Positioner {
+    pub(crate) anchor_rect: (i32, i32, i32, i32),
+    pub(crate) anchor: Anchor,
+    pub(crate) gravity: Gravity,
+    pub(crate) size: (i32, i32),
+    pub(crate) offset: (i32, i32),
+    pub(crate) constraint_adjustment: ConstraintAdjustment,
+}
+
+impl Default for Positioner {
+    fn default() -> Positioner {
+        Positioner {
+            anchor_rect: (0, 0, 1, 1),
+            anchor: Anchor::None,
+            gravity: Gravity::None,
+            size: (1, 1),
+            offset: (0, 0),
+            constraint_adjustment: ConstraintAdjustment::empty(),
+        }
+    }
+}
+
+impl Positioner {
+    /// Start building a new positioner
+    pub fn new() -> Positioner {
+        Positioner::default()
+    }
+
+    /// Set the anchor rectangle, in the parent surface's local coordinates
+    pub fn anchor_rect(mut self, x: i32, y: i32, width: i32, height: i32) -> Positioner {
+        self.anchor_rect = (x, y, width, height);
+        self
+    }
+
+    /// Set the edge (or corner) of the anchor rectangle the popup is anchored to
+    pub fn anchor(mut self, anchor: Anchor) -> Positioner {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Set the direction in which the popup extends away from the anchor point
+    pub fn gravity(mut self, gravity: Gravity) -> Positioner {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Set the size of the popup
+    pub fn size(mut self, width: i32, height: i32) -> Positioner {
+        self.size = (width, height);
+        self
+    }
+
+    /// Set an additional offset from the anchor point
+    pub fn offset(mut self, x: i32, y: i32) -> Positioner {
+        self.offset = (x, y);
+        self
+    }
+
+    /// Set the adjustments the compositor is allowed to make to keep the
+    /// popup on-screen
+    pub fn constraint_adjustment(mut self, constraint_adjustment: ConstraintAdjustment) -> Positioner {
+        self.constraint_adjustment = constraint_adjustment;
+        self
+    }
+
+    /// Resolve this positioner into an absolute `(x, y)` offset from the
+    /// parent surface's origin
+    ///
+    /// This is used by backends (namely legacy `wl_shell`) that have no
+    /// notion of an anchor rectangle or gravity and only accept a plain
+    /// offset for their popups.
+    pub(crate) fn resolve_offset(&self) -> (i32, i32) {
+        let (ax, ay, aw, ah) = self.anchor_rect;
+        let anchor_point = match self.anchor {
+            Anchor::Top => (ax + aw / 2, ay),
+            Anchor::Bottom => (ax + aw / 2, ay + ah),
+            Anchor::Left => (ax, ay + ah / 2),
+            Anchor::Right => (ax + aw, ay + ah / 2),
+            Anchor::TopLeft => (ax, ay),
+            Anchor::TopRight => (ax + aw, ay),
+            Anchor::BottomLeft => (ax, ay + ah),
+            Anchor::BottomRight => (ax + aw, ay + ah),
+            _ => (ax + aw / 2, ay + ah / 2),
+        };
+        let (gx, gy) = match self.gravity {
+            Gravity::Top => (-self.size.0 / 2, -self.size.1),
+            Gravity::Bottom => (-self.size.0 / 2, 0),
+            Gravity::Left => (-self.size.0, -self.size.1 / 2),
+            Gravity::Right => (0, -self.size.1 / 2),
+            Gravity::TopLeft => (-self.size.0, -self.size.1),
+            Gravity::TopRight => (0, -self.size.1),
+            Gravity::BottomLeft => (-self.size.0, 0),
+            Gravity::BottomRight => (0, 0),
+            _ => (-self.size.0 / 2, -self.size.1 / 2),
+        };
+        (anchor_point.0 + gx + self.offset.0, anchor_point.1 + gy + self.offset.1)
+    }
+}
+
+/// Trait abstracting over popup surface protocols
+///
+/// A value implementing this trait is an opaque handle to a live popup;
+/// dropping it destroys the popup on `xdg_shell` and `zxdg_shell_v6`. On
+/// `wl_shell`, which has no request to destroy a shell surface, dropping it
+/// only reverts the surface to a toplevel. All interaction with the popup happens
+/// through the `PopupEvent`s passed to the implementation callback given
+/// to `create_popup`.
+pub trait Popup: Send + Sync {}
+
+/// Creates a popup surface for `shell`, parented onto `parent`
+///
+/// Returns `None` if `parent` is not backed by the same shell protocol as
+/// `shell`, or if `grab` is `None` on the legacy `wl_shell` protocol, which
+/// requires an explicit grab seat and serial to create a popup at all.
+///
+/// No code in the crate calls this function; in particular the higher-level
+/// `Window` abstraction does not create popups.
+pub(crate) fn create_popup<Impl>(
+    shell: &Shell,
+    surface: &Proxy<wl_surface::WlSurface>,
+    parent: &dyn ShellSurface,
+    positioner: Positioner,
+    grab: Option<(&Proxy<wl_seat::WlSeat>, u32)>,
+    implem: Impl,
+) -> Option<Box<Popup>>
+where
+    Impl: FnMut(PopupEvent) + Send + 'static,
+{
+    match *shell {
+        Shell::Wl(ref shell) => wl::WlPopup::create(surface, shell, parent, positioner, grab, implem)
+            .map(|p| Box::new(p) as Box<_>),
+        Shell::Xdg(ref shell) => xdg::XdgPopup::create(surface, shell, parent, positioner, grab, implem)
+            .map(|p| Box::new(p) as Box<_>),
+        Shell::Zxdg(ref shell) => zxdg::ZxdgPopup::create(surface, shell, parent, positioner, grab, implem)
+            .map(|p| Box::new(p) as Box<_>),
+    }
+}
+
 pub(crate) fn create_shell_surface<Impl>(
     shell: &Shell,
     surface: &Proxy<wl_surface::WlSurface>,
@@ -93,10 +288,57 @@ pub trait ShellSurface: Send + Sync {
     fn set_min_size(&self, size: Option<(i32, i32)>);
     /// Set maximum surface size
     fn set_max_size(&self, size: Option<(i32, i32)>);
+    /// Set the parent of this shell surface
+    ///
+    /// Setting the parent lets the compositor know that this surface is a
+    /// dialog, toolbox or other auxiliary window that belongs with `parent`,
+    /// so that it can for example keep them stacked together or raise them
+    /// as a group. Passing `None` clears the relationship.
+    fn set_parent(&self, parent: Option<&dyn ShellSurface>);
     /// Retrive the `XdgToplevel` proxy if the underlying shell surface
     /// uses the `xdg_shell` protocol.
     ///
     /// This allows interactions with other protocol extensions, like
     /// `xdg_decoratins` for example.
     fn get_xdg(&self) -> Option<&Proxy<xdg_toplevel::XdgToplevel>>;
+    /// Retrive the `ZxdgToplevelV6` proxy if the underlying shell surface
+    /// uses the `zxdg_shell_v6` protocol.
+    fn get_zxdg(&self) -> Option<&Proxy<zxdg_toplevel_v6::ZxdgToplevelV6>>;
+    /// Retrive the underlying `WlSurface` if the shell surface uses the
+    /// legacy `wl_shell` protocol.
+    ///
+    /// This is notably used to implement `set_parent()` across backends, as
+    /// `wl_shell_surface.set_transient` is expressed in terms of the parent's
+    /// surface rather than its shell surface.
+    fn get_wl(&self) -> Option<&Proxy<wl_surface::WlSurface>>;
+    /// Retrive the `XdgSurface` proxy if the underlying shell surface
+    /// uses the `xdg_shell` protocol.
+    ///
+    /// This is used to parent a popup created with `create_popup` onto
+    /// this shell surface.
+    fn get_xdg_surface(&self) -> Option<&Proxy<xdg_surface::XdgSurface>>;
+    /// Retrive the `ZxdgSurfaceV6` proxy if the underlying shell surface
+    /// uses the `zxdg_shell_v6` protocol.
+    fn get_zxdg_surface(&self) -> Option<&Proxy<zxdg_surface_v6::ZxdgSurfaceV6>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Anchor, Gravity, Positioner};
+
+    #[test]
+    fn resolve_offset_bottom_anchor_bottom_right_gravity() {
+        let positioner = Positioner::new()
+            .anchor_rect(0, 0, 10, 20)
+            .anchor(Anchor::Bottom)
+            .gravity(Gravity::BottomRight)
+            .size(4, 6);
+        assert_eq!(positioner.resolve_offset(), (5, 20));
+    }
+
+    #[test]
+    fn resolve_offset_falls_back_to_center_without_anchor_or_gravity() {
+        let positioner = Positioner::new().anchor_rect(10, 20, 30, 40).size(8, 8).offset(2, 3);
+        assert_eq!(positioner.resolve_offset(), (23, 39));
+    }
 }
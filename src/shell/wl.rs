@@ -0,0 +1,185 @@
+use wayland_client::protocol::{wl_output, wl_seat, wl_shell, wl_shell_surface, wl_surface};
+use wayland_client::Proxy;
+
+use wayland_protocols::unstable::xdg_shell::v6::client::{zxdg_surface_v6, zxdg_toplevel_v6};
+use wayland_protocols::xdg_shell::client::{xdg_surface, xdg_toplevel};
+
+use super::{Event, Popup, PopupEvent, Positioner, ShellSurface};
+
+pub(crate) struct Wl {
+    surface: Proxy<wl_surface::WlSurface>,
+    shell_surface: Proxy<wl_shell_surface::WlShellSurface>,
+}
+
+impl Wl {
+    pub(crate) fn create<Impl>(
+        surface: &Proxy<wl_surface::WlSurface>,
+        shell: &Proxy<wl_shell::WlShell>,
+        mut implem: Impl,
+    ) -> Wl
+    where
+        Impl: FnMut(Event) + Send + 'static,
+    {
+        let shell_surface = shell
+            .get_shell_surface(surface)
+            .unwrap()
+            .implement(move |event, shell_surface: Proxy<_>| match event {
+                wl_shell_surface::Event::Ping { serial } => {
+                    shell_surface.pong(serial);
+                }
+                wl_shell_surface::Event::Configure { edges: _, width, height } => {
+                    implem(Event::Configure {
+                        new_size: Some((width as u32, height as u32)),
+                        states: Vec::new(),
+                        bounds: None,
+                    });
+                }
+                wl_shell_surface::Event::PopupDone => {}
+            });
+        Wl { surface: surface.clone(), shell_surface }
+    }
+}
+
+impl ShellSurface for Wl {
+    fn resize(&self, seat: &Proxy<wl_seat::WlSeat>, serial: u32, edges: xdg_toplevel::ResizeEdge) {
+        let edges =
+            wl_shell_surface::Resize::from_raw(edges.to_raw()).unwrap_or(wl_shell_surface::Resize::None);
+        self.shell_surface.resize(seat, serial, edges);
+    }
+
+    fn move_(&self, seat: &Proxy<wl_seat::WlSeat>, serial: u32) {
+        self.shell_surface.move_(seat, serial);
+    }
+
+    fn set_title(&self, title: String) {
+        self.shell_surface.set_title(title);
+    }
+
+    fn set_app_id(&self, app_id: String) {
+        self.shell_surface.set_class(app_id);
+    }
+
+    fn set_fullscreen(&self, output: Option<&Proxy<wl_output::WlOutput>>) {
+        self.shell_surface.set_fullscreen(wl_shell_surface::FullscreenMethod::Default, 0, output);
+    }
+
+    fn unset_fullscreen(&self) {
+        self.shell_surface.set_toplevel();
+    }
+
+    fn set_maximized(&self) {
+        self.shell_surface.set_maximized(None);
+    }
+
+    fn unset_maximized(&self) {
+        self.shell_surface.set_toplevel();
+    }
+
+    fn set_minimized(&self) {
+        // not supported by wl_shell_surface
+    }
+
+    fn set_geometry(&self, _x: i32, _y: i32, _width: i32, _height: i32) {
+        // not supported by wl_shell_surface
+    }
+
+    fn set_min_size(&self, _size: Option<(i32, i32)>) {
+        // not supported by wl_shell_surface
+    }
+
+    fn set_max_size(&self, _size: Option<(i32, i32)>) {
+        // not supported by wl_shell_surface
+    }
+
+    fn set_parent(&self, parent: Option<&dyn ShellSurface>) {
+        match parent.and_then(|p| p.get_wl()) {
+            Some(parent_surface) => {
+                // wl_shell_surface has no explicit "clear transient" request,
+                // so re-parenting always goes through set_transient with a
+                // zero offset against the new parent.
+                self.shell_surface.set_transient(parent_surface, 0, 0, wl_shell_surface::Transient::empty());
+            }
+            None => {
+                self.shell_surface.set_toplevel();
+            }
+        }
+    }
+
+    fn get_xdg(&self) -> Option<&Proxy<xdg_toplevel::XdgToplevel>> {
+        None
+    }
+
+    fn get_zxdg(&self) -> Option<&Proxy<zxdg_toplevel_v6::ZxdgToplevelV6>> {
+        None
+    }
+
+    fn get_wl(&self) -> Option<&Proxy<wl_surface::WlSurface>> {
+        Some(&self.surface)
+    }
+
+    fn get_xdg_surface(&self) -> Option<&Proxy<xdg_surface::XdgSurface>> {
+        None
+    }
+
+    fn get_zxdg_surface(&self) -> Option<&Proxy<zxdg_surface_v6::ZxdgSurfaceV6>> {
+        None
+    }
+}
+
+pub(crate) struct WlPopup {
+    surface: Proxy<wl_shell_surface::WlShellSurface>,
+}
+
+impl WlPopup {
+    /// Returns `None` if `parent` is not itself a `wl_shell` surface, or if
+    /// `grab` is `None`: unlike `xdg_shell`/`zxdg_shell_v6`, `wl_shell_surface.set_popup`
+    /// has no grab-less form, so a seat and serial are mandatory here.
+    pub(crate) fn create<Impl>(
+        surface: &Proxy<wl_surface::WlSurface>,
+        shell: &Proxy<wl_shell::WlShell>,
+        parent: &dyn ShellSurface,
+        positioner: Positioner,
+        grab: Option<(&Proxy<wl_seat::WlSeat>, u32)>,
+        mut implem: Impl,
+    ) -> Option<WlPopup>
+    where
+        Impl: FnMut(PopupEvent) + Send + 'static,
+    {
+        let parent_surface = parent.get_wl()?;
+        let (seat, serial) = grab?;
+        let (x, y) = positioner.resolve_offset();
+        let (width, height) = positioner.size;
+
+        // wl_shell has no popup configure event: the geometry is fixed at
+        // creation time, so synthesize a single configure right away.
+        implem(PopupEvent::Configure { x, y, width: width as u32, height: height as u32 });
+
+        let shell_surface = shell
+            .get_shell_surface(surface)
+            .unwrap()
+            .implement(move |event, _| match event {
+                wl_shell_surface::Event::Ping { serial } => {
+                    let _ = serial;
+                }
+                wl_shell_surface::Event::Configure { .. } => {}
+                wl_shell_surface::Event::PopupDone => {
+                    implem(PopupEvent::PopupDone);
+                }
+            });
+
+        shell_surface.set_popup(seat, serial, parent_surface, x, y, wl_shell_surface::Transient::empty());
+
+        Some(WlPopup { surface: shell_surface })
+    }
+}
+
+impl Popup for WlPopup {}
+
+impl Drop for WlPopup {
+    fn drop(&mut self) {
+        // wl_shell_surface has no request of its own to destroy a popup;
+        // reverting it to a plain toplevel is the closest this protocol
+        // gets to tearing one down.
+        self.surface.set_toplevel();
+    }
+}
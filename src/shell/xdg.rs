@@ -0,0 +1,220 @@
+use wayland_client::protocol::{wl_output, wl_seat, wl_surface};
+use wayland_client::Proxy;
+
+use wayland_protocols::unstable::xdg_shell::v6::client::{zxdg_surface_v6, zxdg_toplevel_v6};
+use wayland_protocols::xdg_shell::client::{xdg_popup, xdg_positioner, xdg_surface, xdg_toplevel, xdg_wm_base};
+
+use super::{Event, Popup, PopupEvent, Positioner, ShellSurface};
+
+pub(crate) struct Xdg {
+    surface: Proxy<xdg_surface::XdgSurface>,
+    toplevel: Proxy<xdg_toplevel::XdgToplevel>,
+}
+
+impl Xdg {
+    pub(crate) fn create<Impl>(
+        surface: &Proxy<wl_surface::WlSurface>,
+        shell: &Proxy<xdg_wm_base::XdgWmBase>,
+        mut implem: Impl,
+    ) -> Xdg
+    where
+        Impl: FnMut(Event) + Send + 'static,
+    {
+        let xdg_surface = shell
+            .get_xdg_surface(surface)
+            .unwrap()
+            .implement(|event, xdg_surface: Proxy<_>| match event {
+                xdg_surface::Event::Configure { serial } => {
+                    xdg_surface.ack_configure(serial);
+                }
+            });
+
+        // `configure_bounds` is only re-sent when the bounds actually change,
+        // not before every `configure`, so the last value received is cached
+        // here and reused for every `Event::Configure` until it changes.
+        let mut last_bounds: Option<(u32, u32)> = None;
+
+        let toplevel = xdg_surface
+            .get_toplevel()
+            .unwrap()
+            .implement(move |event, _| match event {
+                xdg_toplevel::Event::Configure { width, height, states } => {
+                    let new_size = if width == 0 || height == 0 {
+                        None
+                    } else {
+                        Some((width as u32, height as u32))
+                    };
+                    let states = states
+                        .chunks_exact(4)
+                        .flat_map(|chunk| {
+                            let value =
+                                u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                            xdg_toplevel::State::from_raw(value)
+                        })
+                        .collect();
+                    implem(Event::Configure { new_size, states, bounds: last_bounds });
+                }
+                xdg_toplevel::Event::Close => {
+                    implem(Event::Close);
+                }
+                xdg_toplevel::Event::ConfigureBounds { width, height } => {
+                    last_bounds = if width == 0 || height == 0 {
+                        None
+                    } else {
+                        Some((width as u32, height as u32))
+                    };
+                }
+            });
+
+        surface.commit();
+
+        Xdg { surface: xdg_surface, toplevel }
+    }
+}
+
+impl ShellSurface for Xdg {
+    fn resize(&self, seat: &Proxy<wl_seat::WlSeat>, serial: u32, edges: xdg_toplevel::ResizeEdge) {
+        self.toplevel.resize(seat, serial, edges);
+    }
+
+    fn move_(&self, seat: &Proxy<wl_seat::WlSeat>, serial: u32) {
+        self.toplevel.move_(seat, serial);
+    }
+
+    fn set_title(&self, title: String) {
+        self.toplevel.set_title(title);
+    }
+
+    fn set_app_id(&self, app_id: String) {
+        self.toplevel.set_app_id(app_id);
+    }
+
+    fn set_fullscreen(&self, output: Option<&Proxy<wl_output::WlOutput>>) {
+        self.toplevel.set_fullscreen(output);
+    }
+
+    fn unset_fullscreen(&self) {
+        self.toplevel.unset_fullscreen();
+    }
+
+    fn set_maximized(&self) {
+        self.toplevel.set_maximized();
+    }
+
+    fn unset_maximized(&self) {
+        self.toplevel.unset_maximized();
+    }
+
+    fn set_minimized(&self) {
+        self.toplevel.set_minimized();
+    }
+
+    fn set_geometry(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.surface.set_window_geometry(x, y, width, height);
+    }
+
+    fn set_min_size(&self, size: Option<(i32, i32)>) {
+        let (w, h) = size.unwrap_or((0, 0));
+        self.toplevel.set_min_size(w, h);
+    }
+
+    fn set_max_size(&self, size: Option<(i32, i32)>) {
+        let (w, h) = size.unwrap_or((0, 0));
+        self.toplevel.set_max_size(w, h);
+    }
+
+    fn set_parent(&self, parent: Option<&dyn ShellSurface>) {
+        let parent_toplevel = parent.and_then(|p| p.get_xdg());
+        self.toplevel.set_parent(parent_toplevel);
+    }
+
+    fn get_xdg(&self) -> Option<&Proxy<xdg_toplevel::XdgToplevel>> {
+        Some(&self.toplevel)
+    }
+
+    fn get_zxdg(&self) -> Option<&Proxy<zxdg_toplevel_v6::ZxdgToplevelV6>> {
+        None
+    }
+
+    fn get_wl(&self) -> Option<&Proxy<wl_surface::WlSurface>> {
+        None
+    }
+
+    fn get_xdg_surface(&self) -> Option<&Proxy<xdg_surface::XdgSurface>> {
+        Some(&self.surface)
+    }
+
+    fn get_zxdg_surface(&self) -> Option<&Proxy<zxdg_surface_v6::ZxdgSurfaceV6>> {
+        None
+    }
+}
+
+pub(crate) struct XdgPopup {
+    surface: Proxy<xdg_surface::XdgSurface>,
+    popup: Proxy<xdg_popup::XdgPopup>,
+}
+
+impl XdgPopup {
+    /// Returns `None` if `parent` is not itself an `xdg_shell` surface.
+    pub(crate) fn create<Impl>(
+        surface: &Proxy<wl_surface::WlSurface>,
+        shell: &Proxy<xdg_wm_base::XdgWmBase>,
+        parent: &dyn ShellSurface,
+        positioner: Positioner,
+        grab: Option<(&Proxy<wl_seat::WlSeat>, u32)>,
+        mut implem: Impl,
+    ) -> Option<XdgPopup>
+    where
+        Impl: FnMut(PopupEvent) + Send + 'static,
+    {
+        let parent_surface = parent.get_xdg_surface()?;
+
+        let xdg_positioner = shell.create_positioner().unwrap().implement(|event, _| match event {});
+        let (ax, ay, aw, ah) = positioner.anchor_rect;
+        xdg_positioner.set_anchor_rect(ax, ay, aw, ah);
+        xdg_positioner.set_anchor(positioner.anchor);
+        xdg_positioner.set_gravity(positioner.gravity);
+        xdg_positioner.set_size(positioner.size.0, positioner.size.1);
+        xdg_positioner.set_offset(positioner.offset.0, positioner.offset.1);
+        xdg_positioner.set_constraint_adjustment(positioner.constraint_adjustment.bits());
+
+        let xdg_surface = shell
+            .get_xdg_surface(surface)
+            .unwrap()
+            .implement(|event, xdg_surface: Proxy<_>| match event {
+                xdg_surface::Event::Configure { serial } => {
+                    xdg_surface.ack_configure(serial);
+                }
+            });
+
+        let popup = xdg_surface
+            .get_popup(Some(parent_surface), &xdg_positioner)
+            .unwrap()
+            .implement(move |event, _| match event {
+                xdg_popup::Event::Configure { x, y, width, height } => {
+                    implem(PopupEvent::Configure { x, y, width: width as u32, height: height as u32 });
+                }
+                xdg_popup::Event::PopupDone => {
+                    implem(PopupEvent::PopupDone);
+                }
+            });
+
+        if let Some((seat, serial)) = grab {
+            popup.grab(seat, serial);
+        }
+
+        surface.commit();
+        xdg_positioner.destroy();
+
+        Some(XdgPopup { surface: xdg_surface, popup })
+    }
+}
+
+impl Popup for XdgPopup {}
+
+impl Drop for XdgPopup {
+    fn drop(&mut self) {
+        self.popup.destroy();
+        self.surface.destroy();
+    }
+}
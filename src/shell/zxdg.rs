@@ -0,0 +1,249 @@
+use wayland_client::protocol::{wl_output, wl_seat, wl_surface};
+use wayland_client::Proxy;
+
+use wayland_protocols::unstable::xdg_shell::v6::client::{
+    zxdg_popup_v6, zxdg_positioner_v6, zxdg_shell_v6, zxdg_surface_v6, zxdg_toplevel_v6,
+};
+use wayland_protocols::xdg_shell::client::xdg_surface;
+use wayland_protocols::xdg_shell::client::xdg_toplevel;
+
+use super::{Anchor, Event, Gravity, Popup, PopupEvent, Positioner, ShellSurface};
+
+/// `xdg_positioner::Anchor` is a sequential enum (`left = 3`, `right = 4`,
+/// `top_left = 5`, ...); `zxdg_positioner_v6::Anchor` is a bitfield
+/// (`top = 1`, `bottom = 2`, `left = 4`, `right = 8`, corners are the
+/// edges OR'd together). The two can't be reinterpreted via raw value
+/// conversion, so map each variant explicitly.
+fn anchor_to_zxdg(anchor: Anchor) -> zxdg_positioner_v6::Anchor {
+    match anchor {
+        Anchor::Top => zxdg_positioner_v6::Anchor::Top,
+        Anchor::Bottom => zxdg_positioner_v6::Anchor::Bottom,
+        Anchor::Left => zxdg_positioner_v6::Anchor::Left,
+        Anchor::Right => zxdg_positioner_v6::Anchor::Right,
+        Anchor::TopLeft => zxdg_positioner_v6::Anchor::Top | zxdg_positioner_v6::Anchor::Left,
+        Anchor::BottomLeft => zxdg_positioner_v6::Anchor::Bottom | zxdg_positioner_v6::Anchor::Left,
+        Anchor::TopRight => zxdg_positioner_v6::Anchor::Top | zxdg_positioner_v6::Anchor::Right,
+        Anchor::BottomRight => zxdg_positioner_v6::Anchor::Bottom | zxdg_positioner_v6::Anchor::Right,
+        _ => zxdg_positioner_v6::Anchor::empty(),
+    }
+}
+
+/// See `anchor_to_zxdg`: `xdg_positioner::Gravity` and
+/// `zxdg_positioner_v6::Gravity` have the same sequential-vs-bitfield
+/// mismatch as their `Anchor` counterparts.
+fn gravity_to_zxdg(gravity: Gravity) -> zxdg_positioner_v6::Gravity {
+    match gravity {
+        Gravity::Top => zxdg_positioner_v6::Gravity::Top,
+        Gravity::Bottom => zxdg_positioner_v6::Gravity::Bottom,
+        Gravity::Left => zxdg_positioner_v6::Gravity::Left,
+        Gravity::Right => zxdg_positioner_v6::Gravity::Right,
+        Gravity::TopLeft => zxdg_positioner_v6::Gravity::Top | zxdg_positioner_v6::Gravity::Left,
+        Gravity::BottomLeft => zxdg_positioner_v6::Gravity::Bottom | zxdg_positioner_v6::Gravity::Left,
+        Gravity::TopRight => zxdg_positioner_v6::Gravity::Top | zxdg_positioner_v6::Gravity::Right,
+        Gravity::BottomRight => zxdg_positioner_v6::Gravity::Bottom | zxdg_positioner_v6::Gravity::Right,
+        _ => zxdg_positioner_v6::Gravity::empty(),
+    }
+}
+
+pub(crate) struct Zxdg {
+    surface: Proxy<zxdg_surface_v6::ZxdgSurfaceV6>,
+    toplevel: Proxy<zxdg_toplevel_v6::ZxdgToplevelV6>,
+}
+
+impl Zxdg {
+    pub(crate) fn create<Impl>(
+        surface: &Proxy<wl_surface::WlSurface>,
+        shell: &Proxy<zxdg_shell_v6::ZxdgShellV6>,
+        mut implem: Impl,
+    ) -> Zxdg
+    where
+        Impl: FnMut(Event) + Send + 'static,
+    {
+        let zxdg_surface = shell
+            .get_xdg_surface(surface)
+            .unwrap()
+            .implement(|event, zxdg_surface: Proxy<_>| match event {
+                zxdg_surface_v6::Event::Configure { serial } => {
+                    zxdg_surface.ack_configure(serial);
+                }
+            });
+
+        let toplevel = zxdg_surface
+            .get_toplevel()
+            .unwrap()
+            .implement(move |event, _| match event {
+                zxdg_toplevel_v6::Event::Configure { width, height, states } => {
+                    let new_size = if width == 0 || height == 0 {
+                        None
+                    } else {
+                        Some((width as u32, height as u32))
+                    };
+                    let states = states
+                        .chunks_exact(4)
+                        .flat_map(|chunk| {
+                            let value =
+                                u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                            xdg_toplevel::State::from_raw(value)
+                        })
+                        .collect();
+                    implem(Event::Configure { new_size, states, bounds: None });
+                }
+                zxdg_toplevel_v6::Event::Close => {
+                    implem(Event::Close);
+                }
+            });
+
+        surface.commit();
+
+        Zxdg { surface: zxdg_surface, toplevel }
+    }
+}
+
+impl ShellSurface for Zxdg {
+    fn resize(&self, seat: &Proxy<wl_seat::WlSeat>, serial: u32, edges: xdg_toplevel::ResizeEdge) {
+        let edges = zxdg_toplevel_v6::ResizeEdge::from_raw(edges.to_raw())
+            .unwrap_or(zxdg_toplevel_v6::ResizeEdge::None);
+        self.toplevel.resize(seat, serial, edges);
+    }
+
+    fn move_(&self, seat: &Proxy<wl_seat::WlSeat>, serial: u32) {
+        self.toplevel.move_(seat, serial);
+    }
+
+    fn set_title(&self, title: String) {
+        self.toplevel.set_title(title);
+    }
+
+    fn set_app_id(&self, app_id: String) {
+        self.toplevel.set_app_id(app_id);
+    }
+
+    fn set_fullscreen(&self, output: Option<&Proxy<wl_output::WlOutput>>) {
+        self.toplevel.set_fullscreen(output);
+    }
+
+    fn unset_fullscreen(&self) {
+        self.toplevel.unset_fullscreen();
+    }
+
+    fn set_maximized(&self) {
+        self.toplevel.set_maximized();
+    }
+
+    fn unset_maximized(&self) {
+        self.toplevel.unset_maximized();
+    }
+
+    fn set_minimized(&self) {
+        self.toplevel.set_minimized();
+    }
+
+    fn set_geometry(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.surface.set_window_geometry(x, y, width, height);
+    }
+
+    fn set_min_size(&self, size: Option<(i32, i32)>) {
+        let (w, h) = size.unwrap_or((0, 0));
+        self.toplevel.set_min_size(w, h);
+    }
+
+    fn set_max_size(&self, size: Option<(i32, i32)>) {
+        let (w, h) = size.unwrap_or((0, 0));
+        self.toplevel.set_max_size(w, h);
+    }
+
+    fn set_parent(&self, parent: Option<&dyn ShellSurface>) {
+        let parent_toplevel = parent.and_then(|p| p.get_zxdg());
+        self.toplevel.set_parent(parent_toplevel);
+    }
+
+    fn get_xdg(&self) -> Option<&Proxy<xdg_toplevel::XdgToplevel>> {
+        None
+    }
+
+    fn get_zxdg(&self) -> Option<&Proxy<zxdg_toplevel_v6::ZxdgToplevelV6>> {
+        Some(&self.toplevel)
+    }
+
+    fn get_wl(&self) -> Option<&Proxy<wl_surface::WlSurface>> {
+        None
+    }
+
+    fn get_xdg_surface(&self) -> Option<&Proxy<xdg_surface::XdgSurface>> {
+        None
+    }
+
+    fn get_zxdg_surface(&self) -> Option<&Proxy<zxdg_surface_v6::ZxdgSurfaceV6>> {
+        Some(&self.surface)
+    }
+}
+
+pub(crate) struct ZxdgPopup {
+    surface: Proxy<zxdg_surface_v6::ZxdgSurfaceV6>,
+    popup: Proxy<zxdg_popup_v6::ZxdgPopupV6>,
+}
+
+impl ZxdgPopup {
+    /// Returns `None` if `parent` is not itself a `zxdg_shell_v6` surface.
+    pub(crate) fn create<Impl>(
+        surface: &Proxy<wl_surface::WlSurface>,
+        shell: &Proxy<zxdg_shell_v6::ZxdgShellV6>,
+        parent: &dyn ShellSurface,
+        positioner: Positioner,
+        grab: Option<(&Proxy<wl_seat::WlSeat>, u32)>,
+        mut implem: Impl,
+    ) -> Option<ZxdgPopup>
+    where
+        Impl: FnMut(PopupEvent) + Send + 'static,
+    {
+        let parent_surface = parent.get_zxdg_surface()?;
+
+        let zxdg_positioner = shell.create_positioner().unwrap().implement(|event, _| match event {});
+        let (ax, ay, aw, ah) = positioner.anchor_rect;
+        zxdg_positioner.set_anchor_rect(ax, ay, aw, ah);
+        zxdg_positioner.set_anchor(anchor_to_zxdg(positioner.anchor));
+        zxdg_positioner.set_gravity(gravity_to_zxdg(positioner.gravity));
+        zxdg_positioner.set_size(positioner.size.0, positioner.size.1);
+        zxdg_positioner.set_offset(positioner.offset.0, positioner.offset.1);
+        zxdg_positioner.set_constraint_adjustment(positioner.constraint_adjustment.bits());
+
+        let zxdg_surface = shell
+            .get_xdg_surface(surface)
+            .unwrap()
+            .implement(|event, zxdg_surface: Proxy<_>| match event {
+                zxdg_surface_v6::Event::Configure { serial } => {
+                    zxdg_surface.ack_configure(serial);
+                }
+            });
+
+        let popup = zxdg_surface
+            .get_popup(parent_surface, &zxdg_positioner)
+            .unwrap()
+            .implement(move |event, _| match event {
+                zxdg_popup_v6::Event::Configure { x, y, width, height } => {
+                    implem(PopupEvent::Configure { x, y, width: width as u32, height: height as u32 });
+                }
+                zxdg_popup_v6::Event::PopupDone => {
+                    implem(PopupEvent::PopupDone);
+                }
+            });
+
+        if let Some((seat, serial)) = grab {
+            popup.grab(seat, serial);
+        }
+
+        surface.commit();
+        zxdg_positioner.destroy();
+
+        Some(ZxdgPopup { surface: zxdg_surface, popup })
+    }
+}
+
+impl Popup for ZxdgPopup {}
+
+impl Drop for ZxdgPopup {
+    fn drop(&mut self) {
+        self.popup.destroy();
+        self.surface.destroy();
+    }
+}